@@ -5,14 +5,19 @@ use dioxus::desktop::{Config, WindowBuilder};
 use dioxus::prelude::*;
 use tracing::Level;
 
-use std::io::Cursor;
-use std::path::Path;
 use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use dicom::core::dictionary::DataDictionary;
+use dicom::core::{DataElement, PrimitiveValue, VR};
+use dicom::dictionary_std::{tags, StandardDataDictionary};
 use dicom::object::open_file;
-use dicom::dictionary_std::tags;
 use dicom::pixeldata::PixelDecoder;
-use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Card {
@@ -22,20 +27,70 @@ pub struct Card {
     studyDate: String,
     modality: String,
     institutionName: String,
-    patientName: String
+    patientName: String,
+    windowCenter: f64,
+    windowWidth: f64,
+    /// Modality-rescaled pixel value range for frame 0, captured once at load time and
+    /// used as stable slider bounds so dragging doesn't shift the range out from under
+    /// the thumb.
+    pixelMin: f64,
+    pixelMax: f64,
+    frameCount: u32,
+    currentFrame: u32,
+    isPlaying: bool,
+    exportFormat: String,
+    jpegQuality: u8,
+    exportFileName: String,
+    exportDataUrl: String,
+    dcmDataUrl: String,
+    tags: Vec<TagEntry>,
+    /// Downscaled (max 256px long edge) preview shown in the grid before a card is opened.
+    thumbnailSrc: String,
+    /// False for batch-ingested cards whose full image/tags/export data hasn't been
+    /// decoded yet; true once `ensure_card_loaded` has populated them.
+    isLoaded: bool,
+}
+
+/// A single element from a loaded object's tag tree, snapshotted so the inspector panel
+/// can be populated without re-reading the file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagEntry {
+    group: u16,
+    element: u16,
+    vr: String,
+    keyword: String,
+    value: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppState {
     isError: bool,
     cards: Option<Vec<Card>>,
+    /// Already-rendered frame data URLs, keyed by file path then frame index, so scrubbing
+    /// a cine loop doesn't re-decode frames that were already shown.
+    frameCache: HashMap<String, HashMap<u32, String>>,
+    /// Already-encoded export data URLs for the card's current export format/quality, keyed
+    /// by file path then frame index, so cine playback and frame scrubbing don't re-encode
+    /// the export on every tick.
+    exportCache: HashMap<String, HashMap<u32, String>>,
+    /// When enabled, each card offers an anonymized .dcm export and the batch "Anonymize
+    /// all" action is shown; the "Download original .dcm" action is unaffected.
+    anonymizeEnabled: bool,
+    /// When enabled, a given PatientID always anonymizes to the same pseudonym.
+    consistentPseudonym: bool,
+    anonymizeMessage: Option<String>,
 }
 
 impl AppState {
     fn new() -> Self {
         Self {
             isError: false,
-            cards: None
+            cards: None,
+            frameCache: HashMap::new(),
+            exportCache: HashMap::new(),
+            anonymizeEnabled: false,
+            consistentPseudonym: true,
+            anonymizeMessage: None,
         }
     }
 }
@@ -43,10 +98,12 @@ impl AppState {
 fn main() {
     // Init logger
     dioxus_logger::init(Level::INFO).expect("failed to init logger");
-    
+
     const VERSION: &str = env!("CARGO_PKG_VERSION");
     let title = format!("dicom dripper v{}", VERSION);
-    LaunchBuilder::desktop().with_cfg(Config::new().with_window(WindowBuilder::new().with_title(title))).launch(App)
+    LaunchBuilder::desktop()
+        .with_cfg(Config::new().with_window(WindowBuilder::new().with_title(title)))
+        .launch(App)
 }
 
 #[component]
@@ -60,6 +117,8 @@ fn App() -> Element {
         link { rel: "stylesheet", href: "bulma.min.css" }
         ErrorMsg {}
         InputFiles{}
+        BatchImport {}
+        AnonymizeSettings {}
         Cards {}
     }
 }
@@ -108,10 +167,134 @@ fn InputFiles() -> Element {
 }
 
 #[component]
-fn Cards() -> Element {
+fn BatchImport() -> Element {
     let app_state = consume_context::<Signal<AppState>>();
+    let mut is_importing = use_signal(|| false);
+
+    rsx! {
+        div {
+            class: "px-3 py-3",
+            button {
+                class: "button is-small mr-2",
+                disabled: is_importing(),
+                onclick: move |_| async move {
+                    if let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await {
+                        is_importing.set(true);
+                        let root = folder.path().to_path_buf();
+                        let results = tokio::task::spawn_blocking(move || {
+                            build_thumbnail_cards(discover_dicom_paths(&root))
+                        })
+                        .await
+                        .unwrap_or_default();
+                        apply_imported_cards(app_state, results);
+                        is_importing.set(false);
+                    }
+                },
+                "Import folder"
+            }
+            button {
+                class: "button is-small",
+                disabled: is_importing(),
+                onclick: move |_| async move {
+                    if let Some(zip_file) = rfd::AsyncFileDialog::new().add_filter("zip", &["zip"]).pick_file().await {
+                        is_importing.set(true);
+                        let zip_path = zip_file.path().to_path_buf();
+                        let results = tokio::task::spawn_blocking(move || {
+                            match extract_zip_dicoms(&zip_path) {
+                                Ok(paths) => build_thumbnail_cards(paths),
+                                Err(e) => {
+                                    tracing::error!("error reading zip archive: {:?}", e);
+                                    Vec::new()
+                                }
+                            }
+                        })
+                        .await
+                        .unwrap_or_default();
+                        apply_imported_cards(app_state, results);
+                        is_importing.set(false);
+                    }
+                },
+                "Import ZIP"
+            }
+            if is_importing() {
+                span { class: "ml-2", "Importing…" }
+            }
+        }
+    }
+}
+
+#[component]
+fn AnonymizeSettings() -> Element {
+    let mut app_state = consume_context::<Signal<AppState>>();
+    let anonymize_enabled = app_state.read().anonymizeEnabled;
+    let consistent_pseudonym = app_state.read().consistentPseudonym;
+    let message = app_state.read().anonymizeMessage.clone();
+
+    rsx! {
+        div {
+            class: "px-3 py-3",
+            label {
+                class: "checkbox mr-4",
+                input {
+                    r#type: "checkbox",
+                    checked: anonymize_enabled,
+                    onchange: move |evt| app_state.write().anonymizeEnabled = evt.checked()
+                }
+                " De-identify exports"
+            }
+            label {
+                class: "checkbox mr-4",
+                input {
+                    r#type: "checkbox",
+                    checked: consistent_pseudonym,
+                    onchange: move |evt| app_state.write().consistentPseudonym = evt.checked()
+                }
+                " Use consistent pseudonyms"
+            }
+            if anonymize_enabled {
+                button {
+                    class: "button is-small is-warning",
+                    onclick: move |_| anonymize_all(app_state),
+                    "Anonymize all"
+                }
+            }
+            if let Some(message) = message {
+                p {
+                    class: "help",
+                    "{message}"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Cards() -> Element {
+    let mut app_state = consume_context::<Signal<AppState>>();
     let cards_state = app_state.read().cards.clone();
 
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            let playing: Vec<(String, u32, u32)> = app_state
+                .read()
+                .cards
+                .as_ref()
+                .map(|cards| {
+                    cards
+                        .iter()
+                        .filter(|c| c.isPlaying && c.frameCount > 1)
+                        .map(|c| (c.filePath.clone(), c.currentFrame, c.frameCount))
+                        .collect()
+                })
+                .unwrap_or_default();
+            for (file_path, current_frame, frame_count) in playing {
+                let next_frame = (current_frame + 1) % frame_count;
+                set_card_frame(app_state, &file_path, next_frame);
+            }
+        }
+    });
+
     match cards_state {
         Some(cards) => {
             rsx! {
@@ -120,25 +303,79 @@ fn Cards() -> Element {
                     div {
                         class: "grid",
                         for card in cards {
-                            div {
-                                class: "cell",
-                                div {
-                                    class: "card",
-                                    header {
-                                        class: "card-header",
-                                        p {
-                                            class: "card-header-title",
-                                            "{card.filePath}"
-                                        }
-                                    }
-                                    div {
-                                        class: "card-image",
-                                        figure {
-                                            class: "image is-4by3",
-                                            img { src: card.imgSrc.clone() }
-                                        }
-                                    }
-                                    div {
+                            CardView { card: card }
+                        }
+                    }
+                }
+            }
+        }
+        None => None,
+    }
+}
+
+/// A single card in the grid. Batch-ingested cards start as a thumbnail-only shell
+/// (`isLoaded == false`) and only decode the full-resolution image, tags, and export
+/// data URLs once the user opens them.
+#[component]
+fn CardView(card: Card) -> Element {
+    let mut app_state = consume_context::<Signal<AppState>>();
+
+    if !card.isLoaded {
+        return rsx! {
+            div {
+                class: "cell",
+                div {
+                    class: "card",
+                    header {
+                        class: "card-header",
+                        p {
+                            class: "card-header-title",
+                            "{card.filePath}"
+                        }
+                    }
+                    div {
+                        class: "card-image",
+                        figure {
+                            class: "image is-4by3",
+                            img { src: card.thumbnailSrc.clone() }
+                        }
+                    }
+                    footer {
+                        class: "card-footer",
+                        button {
+                            class: "card-footer-item button is-small",
+                            onclick: {
+                                let file_path = card.filePath.clone();
+                                move |_| ensure_card_loaded(app_state, &file_path)
+                            },
+                            "Open"
+                        }
+                    }
+                }
+            }
+        };
+    }
+
+    rsx! {
+        div {
+            class: "cell",
+            div {
+                class: "card",
+                header {
+                    class: "card-header",
+                    p {
+                        class: "card-header-title",
+                        "{card.filePath}"
+                    }
+                }
+                div {
+                    class: "card-image",
+                    figure {
+                        class: "image is-4by3",
+                        img { src: card.imgSrc.clone() }
+                    }
+                }
+                div {
                                         class: "card-content",
                                         div {
                                             class: "media",
@@ -158,24 +395,267 @@ fn Cards() -> Element {
                                             class: "content",
                                             "Modality: {card.modality}, Study date: {card.studyDate}"
                                         }
+                                        div {
+                                            class: "field",
+                                            label {
+                                                class: "label is-small",
+                                                "Window center: {card.windowCenter as i64}"
+                                            }
+                                            input {
+                                                class: "slider is-fullwidth",
+                                                r#type: "range",
+                                                min: "{card.pixelMin}",
+                                                max: "{card.pixelMax}",
+                                                step: "1",
+                                                value: "{card.windowCenter}",
+                                                oninput: {
+                                                    let file_path = card.filePath.clone();
+                                                    move |evt| {
+                                                        if let Ok(center) = evt.value().parse::<f64>() {
+                                                            update_card_windowing(app_state, &file_path, Some(center), None);
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        div {
+                                            class: "field",
+                                            label {
+                                                class: "label is-small",
+                                                "Window width: {card.windowWidth as i64}"
+                                            }
+                                            input {
+                                                class: "slider is-fullwidth",
+                                                r#type: "range",
+                                                min: "1",
+                                                max: "{(card.pixelMax - card.pixelMin).max(1.0) * 2.0}",
+                                                step: "1",
+                                                value: "{card.windowWidth}",
+                                                oninput: {
+                                                    let file_path = card.filePath.clone();
+                                                    move |evt| {
+                                                        if let Ok(width) = evt.value().parse::<f64>() {
+                                                            update_card_windowing(app_state, &file_path, None, Some(width));
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        if card.frameCount > 1 {
+                                            div {
+                                                class: "field",
+                                                label {
+                                                    class: "label is-small",
+                                                    "Frame: {card.currentFrame + 1} / {card.frameCount}"
+                                                }
+                                                input {
+                                                    class: "slider is-fullwidth",
+                                                    r#type: "range",
+                                                    min: "0",
+                                                    max: "{card.frameCount - 1}",
+                                                    step: "1",
+                                                    value: "{card.currentFrame}",
+                                                    oninput: {
+                                                        let file_path = card.filePath.clone();
+                                                        move |evt| {
+                                                            if let Ok(frame) = evt.value().parse::<u32>() {
+                                                                set_card_frame(app_state, &file_path, frame);
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                                button {
+                                                    class: "button is-small",
+                                                    onclick: {
+                                                        let file_path = card.filePath.clone();
+                                                        move |_| toggle_playback(app_state, &file_path)
+                                                    },
+                                                    if card.isPlaying { "Pause" } else { "Play" }
+                                                }
+                                            }
+                                        }
+                                        div {
+                                            class: "field",
+                                            label {
+                                                class: "label is-small",
+                                                "Export format"
+                                            }
+                                            div {
+                                                class: "select is-small",
+                                                select {
+                                                    onchange: {
+                                                        let file_path = card.filePath.clone();
+                                                        move |evt| update_card_export(app_state, &file_path, Some(evt.value()), None)
+                                                    },
+                                                    option { value: "png", selected: card.exportFormat == "png", "PNG" }
+                                                    option { value: "jpeg", selected: card.exportFormat == "jpeg", "JPEG" }
+                                                    option { value: "webp", selected: card.exportFormat == "webp", "WebP" }
+                                                    option { value: "tiff", selected: card.exportFormat == "tiff", "TIFF" }
+                                                }
+                                            }
+                                        }
+                                        if card.exportFormat == "jpeg" {
+                                            div {
+                                                class: "field",
+                                                label {
+                                                    class: "label is-small",
+                                                    "JPEG quality: {card.jpegQuality}"
+                                                }
+                                                input {
+                                                    class: "slider is-fullwidth",
+                                                    r#type: "range",
+                                                    min: "1",
+                                                    max: "100",
+                                                    step: "1",
+                                                    value: "{card.jpegQuality}",
+                                                    oninput: {
+                                                        let file_path = card.filePath.clone();
+                                                        move |evt| {
+                                                            if let Ok(quality) = evt.value().parse::<u8>() {
+                                                                update_card_export(app_state, &file_path, None, Some(quality));
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                     footer {
                                         class: "card-footer",
                                         a {
-                                            href: "{card.imgSrc}",
-                                            download: "{card.fileName}.png",
+                                            href: "{card.exportDataUrl}",
+                                            download: "{card.exportFileName}",
                                             class: "card-footer-item",
                                             "Extract image"
                                         }
+                                        a {
+                                            href: "{card.dcmDataUrl}",
+                                            download: "{card.fileName}.dcm",
+                                            class: "card-footer-item",
+                                            "Download original .dcm"
+                                        }
+                                    }
+                                    if app_state.read().anonymizeEnabled {
+                                        AnonymizedExport { card: card.clone(), consistent_pseudonym: app_state.read().consistentPseudonym }
+                                    }
+                TagInspector { card: card.clone() }
+            }
+        }
+    }
+}
+
+#[component]
+fn AnonymizedExport(card: Card, consistent_pseudonym: bool) -> Element {
+    let mut anonymized_url = use_signal(|| None::<String>);
+
+    rsx! {
+        div {
+            class: "card-footer",
+            button {
+                class: "card-footer-item button is-small",
+                onclick: {
+                    let file_path = card.filePath.clone();
+                    move |_| {
+                        match anonymized_dcm_data_url(&file_path, consistent_pseudonym) {
+                            Ok(url) => anonymized_url.set(Some(url)),
+                            Err(e) => tracing::error!("error anonymizing {}: {:?}", file_path, e),
+                        }
+                    }
+                },
+                "Prepare anonymized .dcm"
+            }
+            if let Some(url) = anonymized_url() {
+                a {
+                    href: "{url}",
+                    download: "{card.fileName}_anon.dcm",
+                    class: "card-footer-item",
+                    "Download anonymized .dcm"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TagInspector(card: Card) -> Element {
+    let mut is_open = use_signal(|| false);
+    let mut filter = use_signal(String::new);
+
+    let needle = filter().to_lowercase();
+    let filtered: Vec<TagEntry> = card
+        .tags
+        .iter()
+        .filter(|entry| {
+            needle.is_empty()
+                || entry.keyword.to_lowercase().contains(&needle)
+                || entry.value.to_lowercase().contains(&needle)
+        })
+        .cloned()
+        .collect();
+
+    rsx! {
+        div {
+            class: "card-content",
+            button {
+                class: "button is-small is-fullwidth",
+                onclick: move |_| is_open.set(!is_open()),
+                if is_open() { "Hide tag details" } else { "Show tag details ({card.tags.len()} elements)" }
+            }
+            if is_open() {
+                div {
+                    class: "content mt-2",
+                    input {
+                        class: "input is-small mb-2",
+                        r#type: "text",
+                        placeholder: "Filter by keyword or value…",
+                        value: "{filter}",
+                        oninput: move |evt| filter.set(evt.value())
+                    }
+                    table {
+                        class: "table is-fullwidth is-narrow is-striped",
+                        thead {
+                            tr {
+                                th { "Tag" }
+                                th { "VR" }
+                                th { "Keyword" }
+                                th { "Value" }
+                                th { "" }
+                            }
+                        }
+                        tbody {
+                            for entry in filtered {
+                                tr {
+                                    td { "({entry.group:04X},{entry.element:04X})" }
+                                    td { "{entry.vr}" }
+                                    td { "{entry.keyword}" }
+                                    td { "{entry.value}" }
+                                    td {
+                                        button {
+                                            class: "button is-small",
+                                            onclick: {
+                                                let value = entry.value.clone();
+                                                move |_| {
+                                                    document::eval(&format!("navigator.clipboard.writeText({})", serde_json::Value::String(value.clone())));
+                                                }
+                                            },
+                                            "Copy"
+                                        }
                                     }
                                 }
                             }
                         }
                     }
+                    if let Ok(json_url) = tags_json_data_url(&card.tags) {
+                        a {
+                            href: "{json_url}",
+                            download: "{card.fileName}_tags.json",
+                            class: "button is-small",
+                            "Export all tags as JSON"
+                        }
+                    }
                 }
             }
         }
-        None => None
     }
 }
 
@@ -195,36 +675,936 @@ fn ErrorMsg() -> Element {
                             class: "message-body",
                             "Failed to load file."
                         }
-                    }    
+                    }
                 }
             }
         }
-        false => None
+        false => None,
     }
 }
 
-fn to_card(file_path: &String) -> Result<Card>{
-    let path = Path::new(file_path);
-    let file_name = path.file_stem().unwrap().to_str().unwrap().to_string();
-    let obj = open_file(file_path)?;
+/// Reads `RescaleSlope`/`RescaleIntercept` (defaulting to 1.0/0.0 when absent), converting
+/// stored pixel values into modality values via `stored * slope + intercept`.
+fn modality_rescale(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+) -> (f64, f64) {
+    let slope = obj
+        .element(tags::RESCALE_SLOPE)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .unwrap_or(1.0);
+    let intercept = obj
+        .element(tags::RESCALE_INTERCEPT)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .unwrap_or(0.0);
+    (slope, intercept)
+}
+
+/// Reads the first value of `WindowCenter`/`WindowWidth` when present.
+fn explicit_window(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+) -> Option<(f64, f64)> {
+    let center = obj
+        .element(tags::WINDOW_CENTER)
+        .ok()?
+        .to_multi_str()
+        .ok()?
+        .first()?
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    let width = obj
+        .element(tags::WINDOW_WIDTH)
+        .ok()?
+        .to_multi_str()
+        .ok()?
+        .first()?
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    Some((center, width))
+}
+
+fn is_monochrome1(obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>) -> bool {
+    obj.element(tags::PHOTOMETRIC_INTERPRETATION)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|s| s.trim().eq_ignore_ascii_case("MONOCHROME1"))
+        .unwrap_or(false)
+}
+
+/// Applies the standard linear VOI LUT transform to a modality-rescaled pixel value,
+/// mapping it into the [0, 255] display range for the given window center/width.
+fn apply_voi(modality_value: f64, center: f64, width: f64, invert: bool) -> u8 {
+    let width = width.max(1.0);
+    let low = center - 0.5 - (width - 1.0) / 2.0;
+    let high = center - 0.5 + (width - 1.0) / 2.0;
+    let mut out = if modality_value <= low {
+        0.0
+    } else if modality_value > high {
+        255.0
+    } else {
+        ((modality_value - (center - 0.5)) / (width - 1.0) + 0.5) * 255.0
+    };
+    out = out.clamp(0.0, 255.0);
+    if invert {
+        out = 255.0 - out;
+    }
+    out as u8
+}
+
+/// Reads `NumberOfFrames`, defaulting to 1 for single-frame objects.
+fn number_of_frames(obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>) -> u32 {
+    obj.element(tags::NUMBER_OF_FRAMES)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .and_then(|s| s.trim().parse::<u32>().ok())
+        .unwrap_or(1)
+}
+
+/// A single decode of an object's pixel data, reused by the window-defaulting, slider-range,
+/// and frame-rendering steps so a card load only ever pays for one `decode_pixel_data` call.
+struct DecodedPixels {
+    raw: dicom_pixeldata::ndarray::ArrayD<i32>,
+    rows: u32,
+    columns: u32,
+    samples_per_pixel: u16,
+}
+
+fn decode_pixels(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+) -> Result<DecodedPixels> {
     let pixel_data = obj.decode_pixel_data()?;
-    let img: dicom_pixeldata::image::DynamicImage = pixel_data.to_dynamic_image(0)?;
-    let rgb = img.to_luma8();
+    let raw = pixel_data.to_ndarray::<i32>()?;
+    Ok(DecodedPixels {
+        raw,
+        rows: pixel_data.rows() as u32,
+        columns: pixel_data.columns() as u32,
+        samples_per_pixel: pixel_data.samples_per_pixel(),
+    })
+}
+
+/// Averages a frame's raw samples down to one value per pixel. For monochrome objects
+/// (`samples_per_pixel == 1`) this is the identity; for color (RGB/YBR) objects it collapses
+/// the interleaved channel samples into a single luma-like value, so callers windowing or
+/// ranging the frame agree with what `render_decoded_frame` actually displays.
+fn averaged_pixel_values(
+    frame_view: dicom_pixeldata::ndarray::ArrayViewD<i32>,
+    samples_per_pixel: u16,
+) -> Vec<f64> {
+    let samples = samples_per_pixel.max(1) as usize;
+    let owned;
+    let raw: &[i32] = match frame_view.as_slice() {
+        Some(slice) => slice,
+        None => {
+            owned = frame_view.iter().copied().collect::<Vec<_>>();
+            &owned
+        }
+    };
+    raw.chunks(samples)
+        .map(|chunk| chunk.iter().map(|&v| v as f64).sum::<f64>() / samples as f64)
+        .collect()
+}
+
+/// Renders `frame` of an already-decoded object, applying the modality LUT and the given
+/// VOI window, and returns the result as an 8-bit grayscale image.
+fn render_decoded_frame(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    decoded: &DecodedPixels,
+    frame: u32,
+    center: f64,
+    width: f64,
+) -> dicom_pixeldata::image::GrayImage {
+    let frame_view = decoded
+        .raw
+        .index_axis(dicom_pixeldata::ndarray::Axis(0), frame as usize);
+    let (slope, intercept) = modality_rescale(obj);
+    let invert = is_monochrome1(obj);
+    let values = averaged_pixel_values(frame_view, decoded.samples_per_pixel);
+    let mut img = dicom_pixeldata::image::GrayImage::new(decoded.columns, decoded.rows);
+    for (pixel, average) in img.pixels_mut().zip(values) {
+        let modality_value = average * slope + intercept;
+        *pixel = dicom_pixeldata::image::Luma([apply_voi(modality_value, center, width, invert)]);
+    }
+    img
+}
+
+/// Decodes the given `frame` of `obj`, applies the modality LUT and the given VOI window,
+/// and returns the result as an 8-bit grayscale image.
+fn render_windowed_frame(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    frame: u32,
+    center: f64,
+    width: f64,
+) -> Result<dicom_pixeldata::image::GrayImage> {
+    let decoded = decode_pixels(obj)?;
+    Ok(render_decoded_frame(obj, &decoded, frame, center, width))
+}
+
+/// Picks a sensible default window center/width from an already-decoded frame 0: the
+/// explicit `WindowCenter`/`WindowWidth` tags when present, otherwise one derived from the
+/// modality-rescaled pixel min/max.
+fn default_window_from_decoded(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    decoded: &DecodedPixels,
+) -> (f64, f64) {
+    if let Some(window) = explicit_window(obj) {
+        return window;
+    }
+    let (min, max) = pixel_range_from_decoded(obj, decoded);
+    ((max + min) / 2.0, (max - min).max(1.0))
+}
+
+/// Computes the modality-rescaled pixel value range of an already-decoded frame 0, used as
+/// stable bounds for the window center/width sliders (independent of the current window
+/// settings).
+fn pixel_range_from_decoded(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    decoded: &DecodedPixels,
+) -> (f64, f64) {
+    let frame_view = decoded.raw.index_axis(dicom_pixeldata::ndarray::Axis(0), 0);
+    let (slope, intercept) = modality_rescale(obj);
+    let (mut min, mut max) = (f64::MAX, f64::MIN);
+    for average in averaged_pixel_values(frame_view, decoded.samples_per_pixel) {
+        let modality_value = average * slope + intercept;
+        min = min.min(modality_value);
+        max = max.max(modality_value);
+    }
+    (min, max)
+}
+
+fn data_url_for(img: &dicom_pixeldata::image::GrayImage) -> Result<String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    img.write_to(
+        &mut Cursor::new(&mut bytes),
+        dicom_pixeldata::image::ImageFormat::Png,
+    )?;
+    let b64 = general_purpose::STANDARD.encode(bytes);
+    Ok(format!("data:image/png;base64,{}", b64))
+}
+
+/// The file extension `encode_export` would use for `format`, without re-encoding — used
+/// when a cached export data URL is reused as-is.
+fn export_extension_for(format: &str) -> &'static str {
+    match format {
+        "jpeg" => "jpg",
+        "webp" => "webp",
+        "tiff" => "tiff",
+        _ => "png",
+    }
+}
+
+/// Encodes `img` into the requested export format, returning the bytes alongside the MIME
+/// type and file extension to use for the download. `quality` only affects JPEG output.
+fn encode_export(
+    img: &dicom_pixeldata::image::GrayImage,
+    format: &str,
+    quality: u8,
+) -> Result<(Vec<u8>, &'static str, &'static str)> {
+    let dynamic = dicom_pixeldata::image::DynamicImage::ImageLuma8(img.clone());
     let mut bytes: Vec<u8> = Vec::new();
-    rgb
-    .write_to(&mut Cursor::new(&mut bytes), dicom_pixeldata::image::ImageFormat::Png)
-    .expect("Couldn't write image to bytes.");
+    let (mime, extension) = match format {
+        "jpeg" => {
+            let mut encoder = dicom_pixeldata::image::codecs::jpeg::JpegEncoder::new_with_quality(
+                &mut Cursor::new(&mut bytes),
+                quality.clamp(1, 100),
+            );
+            encoder.encode_image(&dynamic)?;
+            ("image/jpeg", "jpg")
+        }
+        "webp" => {
+            // The image crate's WebP encoder doesn't support grayscale input, so convert
+            // to RGB8 first rather than handing it the Luma8 buffer directly.
+            let rgb = dicom_pixeldata::image::DynamicImage::ImageRgb8(dynamic.to_rgb8());
+            rgb.write_to(
+                &mut Cursor::new(&mut bytes),
+                dicom_pixeldata::image::ImageFormat::WebP,
+            )?;
+            ("image/webp", "webp")
+        }
+        "tiff" => {
+            dynamic.write_to(
+                &mut Cursor::new(&mut bytes),
+                dicom_pixeldata::image::ImageFormat::Tiff,
+            )?;
+            ("image/tiff", "tiff")
+        }
+        _ => {
+            dynamic.write_to(
+                &mut Cursor::new(&mut bytes),
+                dicom_pixeldata::image::ImageFormat::Png,
+            )?;
+            ("image/png", "png")
+        }
+    };
+    Ok((bytes, mime, extension))
+}
+
+fn export_data_url(
+    img: &dicom_pixeldata::image::GrayImage,
+    format: &str,
+    quality: u8,
+) -> Result<(String, &'static str)> {
+    let (bytes, mime, extension) = encode_export(img, format, quality)?;
     let b64 = general_purpose::STANDARD.encode(bytes);
-    let data_url = format!("data:image/png;base64,{}", b64);
-    let tag_study_date = &obj.element(tags::STUDY_DATE)?.to_str()?.to_string();
-    let study_date: String = format!("{}-{}-{}", &tag_study_date[0..4], &tag_study_date[4..6], &tag_study_date[6..8]);
+    Ok((format!("data:{};base64,{}", mime, b64), extension))
+}
+
+/// Reads the untouched source file and packages it as a downloadable `application/dicom`
+/// data URL, for the "download original .dcm" footer action.
+fn dcm_data_url(file_path: &str) -> Result<String> {
+    let bytes = std::fs::read(file_path)?;
+    let b64 = general_purpose::STANDARD.encode(bytes);
+    Ok(format!("data:application/dicom;base64,{}", b64))
+}
+
+fn sha256_hex(input: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Deterministically maps a PatientID to the same replacement token every time, so
+/// anonymized studies from the same patient still group together.
+fn pseudonym_for_patient_id(patient_id: &str) -> String {
+    format!("ANON-{}", &sha256_hex(patient_id)[..12])
+}
+
+/// Deterministically maps a UID to a fresh UID under the 2.25 (UUID-derived) root, so
+/// references between anonymized elements of the same study stay consistent.
+fn pseudonymous_uid(original_uid: &str) -> String {
+    let hex = sha256_hex(original_uid);
+    let as_u128 = u128::from_str_radix(&hex[..32], 16).unwrap_or(0);
+    format!("2.25.{}", as_u128)
+}
+
+fn replace_str_element(
+    obj: &mut dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    tag: dicom::core::Tag,
+    vr: VR,
+    value: String,
+) {
+    obj.put(DataElement::new(tag, vr, PrimitiveValue::from(value)));
+}
+
+const UID_TAGS: &[dicom::core::Tag] = &[
+    tags::STUDY_INSTANCE_UID,
+    tags::SERIES_INSTANCE_UID,
+    tags::SOP_INSTANCE_UID,
+    tags::FRAME_OF_REFERENCE_UID,
+];
+
+/// Blanks or pseudonymizes the PHI-bearing tags on `obj` in place: patient identity,
+/// institution/referring physician, and the study/series/instance UID group.
+fn anonymize_object(
+    obj: &mut dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    consistent_pseudonym: bool,
+) {
+    replace_str_element(obj, tags::PATIENT_NAME, VR::PN, "ANONYMOUS".to_string());
+    if let Some(patient_id) = obj
+        .element(tags::PATIENT_ID)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|s| s.to_string())
+    {
+        let replacement = if consistent_pseudonym {
+            pseudonym_for_patient_id(&patient_id)
+        } else {
+            "ANONYMIZED".to_string()
+        };
+        replace_str_element(obj, tags::PATIENT_ID, VR::LO, replacement);
+    }
+    replace_str_element(obj, tags::PATIENT_BIRTH_DATE, VR::DA, String::new());
+    replace_str_element(obj, tags::INSTITUTION_NAME, VR::LO, String::new());
+    replace_str_element(obj, tags::REFERRING_PHYSICIAN_NAME, VR::PN, String::new());
+    for &tag in UID_TAGS {
+        if let Some(original) = obj
+            .element(tag)
+            .ok()
+            .and_then(|e| e.to_str().ok())
+            .map(|s| s.to_string())
+        {
+            let replacement = pseudonymous_uid(&original);
+            if tag == tags::SOP_INSTANCE_UID {
+                // Keep the File Meta group's copy of the SOP instance UID in sync, or a
+                // strict reader will reject the file over the mismatch with the dataset.
+                obj.meta_mut().media_storage_sop_instance_uid = replacement.clone();
+            }
+            replace_str_element(obj, tag, VR::UI, replacement);
+        }
+    }
+}
+
+/// Re-encodes `file_path` with its PHI-bearing tags anonymized, returning the raw bytes.
+fn anonymized_dcm_bytes(file_path: &str, consistent_pseudonym: bool) -> Result<Vec<u8>> {
+    let mut obj = open_file(file_path)?;
+    anonymize_object(&mut obj, consistent_pseudonym);
+    let mut bytes: Vec<u8> = Vec::new();
+    obj.write_all(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn anonymized_dcm_data_url(file_path: &str, consistent_pseudonym: bool) -> Result<String> {
+    let bytes = anonymized_dcm_bytes(file_path, consistent_pseudonym)?;
+    let b64 = general_purpose::STANDARD.encode(bytes);
+    Ok(format!("data:application/dicom;base64,{}", b64))
+}
+
+/// Anonymizes every loaded card's source file and writes the result alongside the
+/// original as `<name>_anon.dcm`.
+fn anonymize_all(mut app_state: Signal<AppState>) {
+    let (cards, consistent_pseudonym) = {
+        let state = app_state.read();
+        (
+            state.cards.clone().unwrap_or_default(),
+            state.consistentPseudonym,
+        )
+    };
+    let mut written = 0;
+    for card in &cards {
+        match anonymized_dcm_bytes(&card.filePath, consistent_pseudonym) {
+            Ok(bytes) => {
+                let out_path =
+                    Path::new(&card.filePath).with_file_name(format!("{}_anon.dcm", card.fileName));
+                if std::fs::write(&out_path, bytes).is_ok() {
+                    written += 1;
+                }
+            }
+            Err(e) => tracing::error!("error anonymizing {}: {:?}", card.filePath, e),
+        }
+    }
+    app_state.write().anonymizeMessage =
+        Some(format!("Anonymized {written} of {} file(s).", cards.len()));
+}
+
+/// Snapshots each top-level element of `obj`'s data set as a flat list of
+/// tag/VR/keyword/value rows for the per-card tag inspector panel. File meta group elements
+/// are not included, and elements nested inside sequence items are not listed individually.
+fn collect_tags(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+) -> Vec<TagEntry> {
+    let dictionary = StandardDataDictionary;
+    obj.iter()
+        .map(|element| {
+            let tag = element.header().tag;
+            let keyword = dictionary
+                .by_tag(tag)
+                .map(|entry| entry.alias.to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+            let value = element
+                .to_str()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| format!("<{} bytes>", element.value().calculate_byte_len()));
+            TagEntry {
+                group: tag.0,
+                element: tag.1,
+                vr: element.vr().to_string(),
+                keyword,
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Serializes a card's tag snapshot to a downloadable JSON data URL.
+fn tags_json_data_url(tags: &[TagEntry]) -> Result<String> {
+    let json = serde_json::to_string_pretty(tags)?;
+    let b64 = general_purpose::STANDARD.encode(json);
+    Ok(format!("data:application/json;base64,{}", b64))
+}
+
+/// Reads a string element, returning an empty string when the tag is absent or unreadable
+/// instead of failing the whole card. Type-2/3 elements like `InstitutionName` are
+/// legitimately optional, and `discover_dicom_paths`/`extract_zip_dicoms` ingest files by
+/// the "DICM" magic rather than a known IOD, so otherwise-valid files shouldn't be dropped
+/// just because one of these is missing.
+fn optional_str(
+    obj: &dicom::object::FileDicomObject<dicom::object::InMemDicomObject>,
+    tag: dicom::core::Tag,
+) -> String {
+    obj.element(tag)
+        .ok()
+        .and_then(|e| e.to_str().ok())
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
+/// Formats a `DA`-VR date string (`YYYYMMDD`) as `YYYY-MM-DD`, or returns it unchanged if
+/// it's too short to slice — `StudyDate` is Type 2 and may be present but empty.
+fn format_study_date(raw: &str) -> String {
+    if raw.len() < 8 {
+        return raw.to_string();
+    }
+    format!("{}-{}-{}", &raw[0..4], &raw[4..6], &raw[6..8])
+}
+
+fn to_card(file_path: &String) -> Result<Card> {
+    let path = Path::new(file_path);
+    let file_name = path.file_stem().unwrap().to_str().unwrap().to_string();
+    let obj = open_file(file_path)?;
+    let decoded = decode_pixels(&obj)?;
+    let (window_center, window_width) = default_window_from_decoded(&obj, &decoded);
+    let (pixel_min, pixel_max) = pixel_range_from_decoded(&obj, &decoded);
+    let img = render_decoded_frame(&obj, &decoded, 0, window_center, window_width);
+    let data_url = data_url_for(&img)?;
+    let export_format = "png".to_string();
+    let (export_data_url, export_extension) = export_data_url(&img, &export_format, 90)?;
+    let study_date = format_study_date(&optional_str(&obj, tags::STUDY_DATE));
     Ok(Card {
         filePath: file_path.clone(),
-        fileName: file_name,
+        fileName: file_name.clone(),
         imgSrc: data_url,
         studyDate: study_date,
-        modality: obj.element(tags::MODALITY)?.to_str()?.to_string(),
-        institutionName: obj.element(tags::INSTITUTION_NAME)?.to_str()?.to_string(),
-        patientName: obj.element(tags::PATIENT_NAME)?.to_str()?.to_string(),
+        modality: optional_str(&obj, tags::MODALITY),
+        institutionName: optional_str(&obj, tags::INSTITUTION_NAME),
+        patientName: optional_str(&obj, tags::PATIENT_NAME),
+        windowCenter: window_center,
+        windowWidth: window_width,
+        pixelMin: pixel_min,
+        pixelMax: pixel_max,
+        frameCount: number_of_frames(&obj),
+        currentFrame: 0,
+        isPlaying: false,
+        exportFormat: export_format,
+        jpegQuality: 90,
+        exportFileName: format!("{}.{}", file_name, export_extension),
+        exportDataUrl: export_data_url,
+        dcmDataUrl: dcm_data_url(file_path)?,
+        tags: collect_tags(&obj),
+        thumbnailSrc: make_thumbnail(&img)?,
+        isLoaded: true,
+    })
+}
+
+const MAX_THUMBNAIL_EDGE: u32 = 256;
+
+/// Downscales `img` so its long edge is at most `MAX_THUMBNAIL_EDGE` pixels, for the
+/// card grid preview.
+fn make_thumbnail(img: &dicom_pixeldata::image::GrayImage) -> Result<String> {
+    let (width, height) = (img.width(), img.height());
+    let long_edge = width.max(height);
+    let thumbnail = if long_edge > MAX_THUMBNAIL_EDGE {
+        let scale = MAX_THUMBNAIL_EDGE as f64 / long_edge as f64;
+        let new_width = ((width as f64 * scale).round() as u32).max(1);
+        let new_height = ((height as f64 * scale).round() as u32).max(1);
+        dicom_pixeldata::image::imageops::resize(
+            img,
+            new_width,
+            new_height,
+            dicom_pixeldata::image::imageops::FilterType::Triangle,
+        )
+    } else {
+        img.clone()
+    };
+    data_url_for(&thumbnail)
+}
+
+/// Builds a thumbnail-only card for batch ingestion: decodes frame 0 just far enough to
+/// render a grid preview, deferring the full windowed image, tag snapshot, and export
+/// data URLs until the card is opened via `ensure_card_loaded`.
+fn to_thumbnail_card(file_path: &str) -> Result<Card> {
+    let path = Path::new(file_path);
+    let file_name = path.file_stem().unwrap().to_str().unwrap().to_string();
+    let obj = open_file(file_path)?;
+    let decoded = decode_pixels(&obj)?;
+    let (window_center, window_width) = default_window_from_decoded(&obj, &decoded);
+    let (pixel_min, pixel_max) = pixel_range_from_decoded(&obj, &decoded);
+    let img = render_decoded_frame(&obj, &decoded, 0, window_center, window_width);
+    let thumbnail = make_thumbnail(&img)?;
+    let study_date = format_study_date(&optional_str(&obj, tags::STUDY_DATE));
+    Ok(Card {
+        filePath: file_path.to_string(),
+        fileName: file_name,
+        imgSrc: thumbnail.clone(),
+        studyDate: study_date,
+        modality: optional_str(&obj, tags::MODALITY),
+        institutionName: optional_str(&obj, tags::INSTITUTION_NAME),
+        patientName: optional_str(&obj, tags::PATIENT_NAME),
+        windowCenter: window_center,
+        windowWidth: window_width,
+        pixelMin: pixel_min,
+        pixelMax: pixel_max,
+        frameCount: number_of_frames(&obj),
+        currentFrame: 0,
+        isPlaying: false,
+        exportFormat: "png".to_string(),
+        jpegQuality: 90,
+        exportFileName: String::new(),
+        exportDataUrl: String::new(),
+        dcmDataUrl: String::new(),
+        tags: Vec::new(),
+        thumbnailSrc: thumbnail,
+        isLoaded: false,
     })
-}
\ No newline at end of file
+}
+
+/// Fully decodes the card at `file_path` in place if it was ingested as a thumbnail-only
+/// shell, populating the windowed image, tag snapshot, and export data URLs.
+fn ensure_card_loaded(mut app_state: Signal<AppState>, file_path: &str) {
+    let needs_load = app_state
+        .read()
+        .cards
+        .as_ref()
+        .and_then(|cards| cards.iter().find(|c| c.filePath == file_path))
+        .map(|c| !c.isLoaded)
+        .unwrap_or(false);
+    if !needs_load {
+        return;
+    }
+    match to_card(&file_path.to_string()) {
+        Ok(full_card) => {
+            let mut state = app_state.write();
+            if let Some(card) = state
+                .cards
+                .as_mut()
+                .and_then(|cards| cards.iter_mut().find(|c| c.filePath == file_path))
+            {
+                *card = full_card;
+            }
+        }
+        Err(e) => tracing::error!("error loading {}: {:?}", file_path, e),
+    }
+}
+
+/// Recursively finds DICOM files under `root` by checking for the "DICM" magic at byte
+/// offset 128, so extensionless files from scanners are picked up rather than relying on
+/// the `.dcm` extension.
+fn discover_dicom_paths(root: &Path) -> Vec<std::path::PathBuf> {
+    let mut found = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if is_dicom_file(&path) {
+                found.push(path);
+            }
+        }
+    }
+    found
+}
+
+fn is_dicom_file(path: &Path) -> bool {
+    use std::io::Read;
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut header = [0u8; 132];
+    if file.read_exact(&mut header).is_err() {
+        return false;
+    }
+    &header[128..132] == b"DICM"
+}
+
+/// Reads a `.zip` archive's entries into memory, then writes any entry that carries the
+/// "DICM" magic out to a scratch directory on disk, since the rest of the pipeline (card
+/// loading, anonymized export, the original-`.dcm` download) works from file paths rather
+/// than in-memory buffers.
+fn extract_zip_dicoms(zip_path: &Path) -> Result<Vec<std::path::PathBuf>> {
+    use std::io::Read;
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let extract_dir = std::env::temp_dir().join(format!(
+        "dicom-dripper-{}",
+        zip_path.file_stem().unwrap_or_default().to_string_lossy()
+    ));
+    std::fs::create_dir_all(&extract_dir)?;
+    let mut found = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut buf = Vec::new();
+        entry.read_to_end(&mut buf)?;
+        if buf.len() < 132 || &buf[128..132] != b"DICM" {
+            continue;
+        }
+        let out_path = extract_dir.join(format!("entry-{}.dcm", i));
+        std::fs::write(&out_path, &buf)?;
+        found.push(out_path);
+    }
+    Ok(found)
+}
+
+/// Builds thumbnail cards for `paths` in parallel. Pure (touches no `AppState`) so callers
+/// can run it on a blocking task off the UI thread and apply the results afterward.
+fn build_thumbnail_cards(paths: Vec<std::path::PathBuf>) -> Vec<Result<Card>> {
+    use rayon::prelude::*;
+    paths
+        .par_iter()
+        .map(|path| to_thumbnail_card(path.to_string_lossy().as_ref()))
+        .collect()
+}
+
+/// Replaces the current card list with the results of `build_thumbnail_cards`.
+fn apply_imported_cards(mut app_state: Signal<AppState>, results: Vec<Result<Card>>) {
+    let mut cards = Vec::new();
+    let mut had_error = false;
+    for result in results {
+        match result {
+            Ok(card) => cards.push(card),
+            Err(e) => {
+                tracing::error!("error: {:?}", e);
+                had_error = true;
+            }
+        }
+    }
+    *app_state.write() = AppState::new();
+    app_state.write().cards = Some(cards);
+    app_state.write().isError = had_error;
+}
+
+/// Encodes `img` as `card`'s export and writes the result onto `card`'s export fields,
+/// returning the data URL on success so the caller can also populate `exportCache` — shared
+/// by `update_card_export` and `update_card_windowing` so both only ever encode a frame once.
+fn apply_card_export(
+    card: &mut Card,
+    img: &dicom_pixeldata::image::GrayImage,
+    format: String,
+    quality: u8,
+) -> Option<String> {
+    match export_data_url(img, &format, quality) {
+        Ok((data_url, extension)) => {
+            card.exportFormat = format;
+            card.jpegQuality = quality;
+            card.exportFileName = format!("{}.{}", card.fileName, extension);
+            card.exportDataUrl = data_url.clone();
+            Some(data_url)
+        }
+        Err(e) => {
+            tracing::error!("error encoding export: {:?}", e);
+            None
+        }
+    }
+}
+
+/// Re-encodes the currently displayed frame of the card for `file_path` into an updated
+/// export format and/or JPEG quality, regenerating the download data URL in place. Since
+/// the format/quality changed, any cached exports for this file no longer apply.
+fn update_card_export(
+    mut app_state: Signal<AppState>,
+    file_path: &str,
+    format: Option<String>,
+    quality: Option<u8>,
+) {
+    let Some(obj) = open_file(file_path).ok() else {
+        return;
+    };
+    let mut state = app_state.write();
+    state.exportCache.remove(file_path);
+    let Some(cards) = state.cards.as_mut() else {
+        return;
+    };
+    let Some(card) = cards.iter_mut().find(|c| c.filePath == file_path) else {
+        return;
+    };
+    let new_format = format.unwrap_or_else(|| card.exportFormat.clone());
+    let new_quality = quality.unwrap_or(card.jpegQuality);
+    let current_frame = card.currentFrame;
+    let img = match render_windowed_frame(&obj, current_frame, card.windowCenter, card.windowWidth)
+    {
+        Ok(img) => img,
+        Err(e) => {
+            tracing::error!("error rendering window: {:?}", e);
+            return;
+        }
+    };
+    let cached_entry = apply_card_export(card, &img, new_format, new_quality);
+    if let Some(data_url) = cached_entry {
+        state
+            .exportCache
+            .entry(file_path.to_string())
+            .or_default()
+            .insert(current_frame, data_url);
+    }
+}
+
+/// Renders `frame` of `file_path` under the given window, reusing the cached data URL in
+/// `AppState` when one was already rendered for that exact (file, frame) pair.
+fn render_frame_cached(
+    app_state: &mut Signal<AppState>,
+    file_path: &str,
+    frame: u32,
+    center: f64,
+    width: f64,
+) -> Result<String> {
+    if let Some(cached) = app_state
+        .read()
+        .frameCache
+        .get(file_path)
+        .and_then(|frames| frames.get(&frame))
+    {
+        return Ok(cached.clone());
+    }
+    let obj = open_file(file_path)?;
+    let img = render_windowed_frame(&obj, frame, center, width)?;
+    let data_url = data_url_for(&img)?;
+    app_state
+        .write()
+        .frameCache
+        .entry(file_path.to_string())
+        .or_default()
+        .insert(frame, data_url.clone());
+    Ok(data_url)
+}
+
+/// Renders the export data URL for `frame` of `file_path` under the card's current export
+/// format/quality, reusing the cached result in `AppState` when one was already encoded for
+/// that exact (file, frame) pair, so cine playback and frame scrubbing don't re-encode on
+/// every tick.
+fn render_export_cached(
+    app_state: &mut Signal<AppState>,
+    file_path: &str,
+    frame: u32,
+    center: f64,
+    width: f64,
+    format: &str,
+    quality: u8,
+) -> Result<String> {
+    if let Some(cached) = app_state
+        .read()
+        .exportCache
+        .get(file_path)
+        .and_then(|frames| frames.get(&frame))
+    {
+        return Ok(cached.clone());
+    }
+    let obj = open_file(file_path)?;
+    let img = render_windowed_frame(&obj, frame, center, width)?;
+    let (data_url, _extension) = export_data_url(&img, format, quality)?;
+    app_state
+        .write()
+        .exportCache
+        .entry(file_path.to_string())
+        .or_default()
+        .insert(frame, data_url.clone());
+    Ok(data_url)
+}
+
+/// Re-renders the card for `file_path` with an updated window center and/or width,
+/// regenerating its base64 data URL in place. Any frames cached under the old window
+/// are dropped since they no longer reflect the current windowing.
+fn update_card_windowing(
+    mut app_state: Signal<AppState>,
+    file_path: &str,
+    center: Option<f64>,
+    width: Option<f64>,
+) {
+    let Some(obj) = open_file(file_path).ok() else {
+        return;
+    };
+    let Some(decoded) = decode_pixels(&obj).ok() else {
+        return;
+    };
+    let mut state = app_state.write();
+    state.frameCache.remove(file_path);
+    state.exportCache.remove(file_path);
+    let Some(cards) = state.cards.as_mut() else {
+        return;
+    };
+    let Some(card) = cards.iter_mut().find(|c| c.filePath == file_path) else {
+        return;
+    };
+    let new_center = center.unwrap_or(card.windowCenter);
+    let new_width = width.unwrap_or(card.windowWidth);
+    let current_frame = card.currentFrame;
+    let format = card.exportFormat.clone();
+    let quality = card.jpegQuality;
+
+    // Render the windowed frame once and reuse it for both the display URL and the export
+    // encode, rather than decoding the object a second time for the export.
+    let img = render_decoded_frame(&obj, &decoded, current_frame, new_center, new_width);
+
+    match data_url_for(&img) {
+        Ok(data_url) => {
+            card.windowCenter = new_center;
+            card.windowWidth = new_width;
+            card.imgSrc = data_url;
+        }
+        Err(e) => tracing::error!("error rendering window: {:?}", e),
+    }
+
+    let cached_export = apply_card_export(card, &img, format, quality);
+    if let Some(data_url) = cached_export {
+        state
+            .exportCache
+            .entry(file_path.to_string())
+            .or_default()
+            .insert(current_frame, data_url);
+    }
+}
+
+/// Scrubs the card for `file_path` to `frame`, updating `currentFrame`, `imgSrc`, and the
+/// export data URL from their respective per-frame caches so cine playback and scrubbing
+/// never re-decode or re-encode a frame that was already rendered.
+fn set_card_frame(mut app_state: Signal<AppState>, file_path: &str, frame: u32) {
+    let card = app_state
+        .read()
+        .cards
+        .as_ref()
+        .and_then(|cards| cards.iter().find(|c| c.filePath == file_path).cloned());
+    let Some(card) = card else { return };
+    let rendered = render_frame_cached(
+        &mut app_state,
+        file_path,
+        frame,
+        card.windowCenter,
+        card.windowWidth,
+    )
+    .and_then(|img_src| {
+        let export_data_url = render_export_cached(
+            &mut app_state,
+            file_path,
+            frame,
+            card.windowCenter,
+            card.windowWidth,
+            &card.exportFormat,
+            card.jpegQuality,
+        )?;
+        Ok((img_src, export_data_url))
+    });
+    match rendered {
+        Ok((img_src, export_data_url)) => {
+            let mut state = app_state.write();
+            if let Some(card) = state
+                .cards
+                .as_mut()
+                .and_then(|cards| cards.iter_mut().find(|c| c.filePath == file_path))
+            {
+                card.currentFrame = frame;
+                card.imgSrc = img_src;
+                card.exportDataUrl = export_data_url;
+                card.exportFileName = format!(
+                    "{}.{}",
+                    card.fileName,
+                    export_extension_for(&card.exportFormat)
+                );
+            }
+        }
+        Err(e) => tracing::error!("error decoding frame {}: {:?}", frame, e),
+    }
+}
+
+/// Toggles cine playback for the card at `file_path`.
+fn toggle_playback(mut app_state: Signal<AppState>, file_path: &str) {
+    let mut state = app_state.write();
+    if let Some(card) = state
+        .cards
+        .as_mut()
+        .and_then(|cards| cards.iter_mut().find(|c| c.filePath == file_path))
+    {
+        card.isPlaying = !card.isPlaying;
+    }
+}